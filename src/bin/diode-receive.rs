@@ -1,10 +1,10 @@
-use clap::{Arg, Command};
+use clap::{Arg, ArgAction, Command};
 use crossbeam_channel::{unbounded, SendError};
 use diode::receive::{decoding, dispatch, reblock};
-use diode::{protocol, sock_utils, udp};
+use diode::{protocol, sock_utils, stats, udp};
 use log::{error, info};
 use raptorq::EncodingPacket;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 use std::{
     env, fmt, io,
     net::{self, SocketAddr, UdpSocket},
@@ -14,7 +14,7 @@ use std::{
 };
 
 struct Config {
-    from_udp: SocketAddr,
+    from_udp: Vec<SocketAddr>,
     from_udp_mtu: u16,
 
     nb_clients: u16,
@@ -28,6 +28,11 @@ struct Config {
     to_tcp: SocketAddr,
     abort_timeout: Duration,
     heartbeat: Duration,
+
+    tun: Option<String>,
+    tun_mtu: u16,
+
+    stats_interval: Option<Duration>,
 }
 
 impl Config {
@@ -52,7 +57,8 @@ fn command_args() -> Config {
                 .long("from_udp")
                 .value_name("ip:port")
                 .default_value("127.0.0.1:6000")
-                .help("From where to read data"),
+                .action(ArgAction::Append)
+                .help("From where to read data; repeat to listen on several paths"),
         )
         .arg(
             Arg::new("from_udp_mtu")
@@ -125,10 +131,34 @@ fn command_args() -> Config {
                 .value_parser(clap::value_parser!(u16))
                 .help("Duration in seconds between heartbeat messages"),
         )
+        .arg(
+            Arg::new("tun")
+                .long("tun")
+                .value_name("ifname")
+                .help("Write reassembled traffic to this TUN interface instead of --to_tcp"),
+        )
+        .arg(
+            Arg::new("mtu")
+                .long("mtu")
+                .value_name("nb_bytes")
+                .default_value("1500")
+                .value_parser(clap::value_parser!(u16))
+                .help("MTU of the outgoing TUN interface"),
+        )
+        .arg(
+            Arg::new("stats_interval")
+                .long("stats_interval")
+                .value_name("nb_secs")
+                .value_parser(clap::value_parser!(u64))
+                .help("Duration in seconds between throughput and FEC effectiveness logs"),
+        )
         .get_matches();
 
-    let from_udp = SocketAddr::from_str(args.get_one::<String>("from_udp").expect("default"))
-        .expect("invalid from_udp_parameter");
+    let from_udp = args
+        .get_many::<String>("from_udp")
+        .expect("default")
+        .map(|addr| SocketAddr::from_str(addr).expect("invalid from_udp parameter"))
+        .collect();
     let from_udp_mtu = *args.get_one::<u16>("from_udp_mtu").expect("default");
     let nb_clients = *args.get_one::<u16>("nb_clients").expect("default");
     let nb_decoding_threads = *args.get_one::<u8>("nb_decoding_threads").expect("default");
@@ -141,6 +171,11 @@ fn command_args() -> Config {
     let abort_timeout =
         Duration::from_secs(*args.get_one::<u64>("abort_timeout").expect("default"));
     let heartbeat = *args.get_one::<u16>("heartbeat").expect("default");
+    let tun = args.get_one::<String>("tun").cloned();
+    let tun_mtu = *args.get_one::<u16>("mtu").expect("default");
+    let stats_interval = args
+        .get_one::<u64>("stats_interval")
+        .map(|secs| Duration::from_secs(*secs));
 
     Config {
         from_udp,
@@ -153,6 +188,9 @@ fn command_args() -> Config {
         to_tcp,
         abort_timeout,
         heartbeat: Duration::from_secs(heartbeat as u64),
+        tun,
+        tun_mtu,
+        stats_interval,
     }
 }
 
@@ -195,19 +233,34 @@ fn main_loop(config: Config) -> Result<(), Error> {
     let (reblock_sendq, reblock_recvq) = unbounded::<(u8, Vec<EncodingPacket>)>();
     let (udp_sendq, udp_recvq) = unbounded::<Vec<EncodingPacket>>();
 
+    let stats = Arc::new(stats::Stats::default());
+
+    let sink = match &config.tun {
+        Some(ifname) => dispatch::Sink::Tun {
+            ifname: ifname.clone(),
+            mtu: config.tun_mtu,
+        },
+        None => dispatch::Sink::Tcp {
+            to_tcp: config.to_tcp,
+            to_tcp_buffer_size: config.encoding_block_size as usize
+                - protocol::Message::serialize_overhead(),
+            abort_timeout: config.abort_timeout,
+        },
+    };
+
     let dispatch_config = dispatch::Config {
         nb_multiplex: config.nb_clients,
-        to_tcp: config.to_tcp,
-        to_tcp_buffer_size: config.encoding_block_size as usize
-            - protocol::Message::serialize_overhead(),
-        abort_timeout: config.abort_timeout,
+        sink,
         heartbeat: config.heartbeat,
     };
 
-    thread::Builder::new()
-        .name("diode-dispatch".to_string())
-        .spawn(move || dispatch::new(dispatch_config, decoding_recvq))
-        .expect("thread spawn");
+    {
+        let stats = Arc::clone(&stats);
+        thread::Builder::new()
+            .name("diode-dispatch".to_string())
+            .spawn(move || dispatch::new(dispatch_config, decoding_recvq, stats))
+            .expect("thread spawn");
+    }
 
     let object_transmission_info =
         protocol::object_transmission_information(config.from_udp_mtu, config.encoding_block_size);
@@ -222,33 +275,42 @@ fn main_loop(config: Config) -> Result<(), Error> {
         flush_timeout: config.flush_timeout,
     };
 
-    info!(
-        "sending TCP traffic to {} with abort timeout of {} second(s) and {} simultaneous transfers",
-        config.to_tcp,
-        config.abort_timeout.as_secs(),
-        config.nb_clients,
-    );
+    match &config.tun {
+        Some(ifname) => info!(
+            "writing reassembled traffic to TUN interface {ifname} with {} simultaneous transfers",
+            config.nb_clients,
+        ),
+        None => info!(
+            "sending TCP traffic to {} with abort timeout of {} second(s) and {} simultaneous transfers",
+            config.to_tcp,
+            config.abort_timeout.as_secs(),
+            config.nb_clients,
+        ),
+    }
 
     let max_messages = protocol::nb_encoding_packets(&object_transmission_info) as u16
         + protocol::nb_repair_packets(&object_transmission_info, config.repair_block_size) as u16;
 
-    info!("listening for UDP packets at {}", config.from_udp);
-    let socket = UdpSocket::bind(config.from_udp)?;
-    sock_utils::set_socket_recv_buffer_size(&socket, i32::MAX)?;
-    let sock_buffer_size = sock_utils::get_socket_recv_buffer_size(&socket)?;
-    log::info!("UDP socket receive buffer size set to {sock_buffer_size}");
-    if (sock_buffer_size as u64)
-        < 2 * (config.encoding_block_size + config.repair_block_size as u64)
-    {
-        log::warn!("UDP socket recv buffer may be too small to achieve optimal performances");
-        log::warn!("Please review the kernel parameters using sysctl");
-    }
+    let mut udp_receivers = Vec::with_capacity(config.from_udp.len());
+    for from_udp in &config.from_udp {
+        info!("listening for UDP packets at {from_udp}");
+        let socket = UdpSocket::bind(from_udp)?;
+        sock_utils::set_socket_recv_buffer_size(&socket, i32::MAX)?;
+        let sock_buffer_size = sock_utils::get_socket_recv_buffer_size(&socket)?;
+        log::info!("UDP socket receive buffer size set to {sock_buffer_size}");
+        if (sock_buffer_size as u64)
+            < 2 * (config.encoding_block_size + config.repair_block_size as u64)
+        {
+            log::warn!("UDP socket recv buffer may be too small to achieve optimal performances");
+            log::warn!("Please review the kernel parameters using sysctl");
+        }
 
-    let mut udp_messages = udp::UdpMessages::new_receiver(
-        socket,
-        usize::from(max_messages),
-        usize::from(config.from_udp_mtu),
-    );
+        udp_receivers.push(udp::UdpMessages::new_receiver(
+            socket,
+            usize::from(max_messages),
+            usize::from(config.from_udp_mtu),
+        ));
+    }
 
     let block_to_receive = Mutex::new(0);
 
@@ -266,6 +328,7 @@ fn main_loop(config: Config) -> Result<(), Error> {
             .expect("thread spawn");
 
         for i in 0..config.nb_decoding_threads {
+            let stats = Arc::clone(&stats);
             thread::Builder::new()
                 .name(format!("diode-decoding_{i}"))
                 .spawn_scoped(scope, || {
@@ -274,18 +337,66 @@ fn main_loop(config: Config) -> Result<(), Error> {
                         &block_to_receive,
                         &reblock_recvq,
                         &decoding_sendq,
+                        &stats,
                     )
                 })
                 .expect("thread spawn");
         }
 
-        loop {
-            let packets = udp_messages.recv_mmsg()?.map(EncodingPacket::deserialize);
-            udp_sendq.send(packets.collect())?;
+        if let Some(stats_interval) = config.stats_interval {
+            let stats = Arc::clone(&stats);
+            let stats_config = stats::Config {
+                interval: stats_interval,
+                nb_encoding_packets: protocol::nb_encoding_packets(&object_transmission_info),
+            };
+            thread::Builder::new()
+                .name("diode-stats".to_string())
+                .spawn_scoped(scope, move || stats::new(stats_config, &stats))
+                .expect("thread spawn");
         }
+
+        // Every path feeds the same udp_sendq: RaptorQ packets already carry
+        // their block/ESI, so reblock can regroup packets received out of
+        // order across paths.
+        let (last_udp_messages, other_udp_messages) = udp_receivers
+            .split_last_mut()
+            .expect("at least one from_udp path");
+
+        for udp_messages in other_udp_messages {
+            let udp_sendq = udp_sendq.clone();
+            let stats = Arc::clone(&stats);
+            thread::Builder::new()
+                .name("diode-udp-recv".to_string())
+                .spawn_scoped(scope, move || {
+                    if let Err(e) = udp_recv_loop(udp_messages, &udp_sendq, &stats) {
+                        error!("UDP receive loop error: {e}");
+                    }
+                })
+                .expect("thread spawn");
+        }
+
+        udp_recv_loop(last_udp_messages, &udp_sendq, &stats)
     })
 }
 
+fn udp_recv_loop(
+    udp_messages: &mut udp::UdpMessages,
+    udp_sendq: &crossbeam_channel::Sender<Vec<EncodingPacket>>,
+    stats: &stats::Stats,
+) -> Result<(), Error> {
+    loop {
+        let packets: Vec<_> = udp_messages
+            .recv_mmsg()?
+            .map(EncodingPacket::deserialize)
+            .collect();
+        stats.record_udp_recv(
+            packets.len() as u64,
+            packets.iter().map(|p| p.data().len() as u64).sum(),
+        );
+        udp_sendq.send(packets)?;
+    }
+}
+
 fn main() {
     let mut config = command_args();
 
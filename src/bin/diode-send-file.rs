@@ -21,6 +21,12 @@ fn main() {
                 .value_parser(clap::value_parser!(usize))
                 .help("Size of file read/TCP write buffer"),
         )
+        .arg(
+            Arg::new("mmap")
+                .long("mmap")
+                .action(ArgAction::SetTrue)
+                .help("Memory-map regular files instead of reading them through a heap buffer"),
+        )
         .arg(
             Arg::new("file")
                 .action(ArgAction::Append)
@@ -32,11 +38,13 @@ fn main() {
     let to_tcp = SocketAddr::from_str(args.get_one::<String>("to_tcp").expect("default"))
         .expect("invalid to_tcp parameter");
     let buffer_size = *args.get_one::<usize>("buffer_size").expect("default");
+    let mmap = args.get_flag("mmap");
     let files = args.get_many("file").expect("required").cloned().collect();
 
     let config = file::Config {
         socket_addr: to_tcp,
         buffer_size,
+        mmap,
     };
 
     init_logger();
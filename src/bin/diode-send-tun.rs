@@ -0,0 +1,52 @@
+use clap::{Arg, Command};
+use diode::tun_send;
+use log::error;
+use std::{env, net::SocketAddr, str::FromStr};
+
+fn main() {
+    let args = Command::new(env!("CARGO_BIN_NAME"))
+        .version(env!("CARGO_PKG_VERSION"))
+        .arg(
+            Arg::new("to_tcp")
+                .long("to_tcp")
+                .value_name("ip:port")
+                .default_value("127.0.0.1:5000")
+                .help("Address and port to connect to diode-send"),
+        )
+        .arg(
+            Arg::new("tun")
+                .long("tun")
+                .value_name("ifname")
+                .default_value("tun0")
+                .help("Name of the TUN device to read IP packets from"),
+        )
+        .arg(
+            Arg::new("mtu")
+                .long("mtu")
+                .value_name("nb_bytes")
+                .default_value("1500")
+                .value_parser(clap::value_parser!(u16))
+                .help("MTU of the TUN device"),
+        )
+        .get_matches();
+
+    let to_tcp = SocketAddr::from_str(args.get_one::<String>("to_tcp").expect("default"))
+        .expect("invalid to_tcp parameter");
+    let tun = args.get_one::<String>("tun").expect("default").clone();
+    let mtu = *args.get_one::<u16>("mtu").expect("default");
+
+    let config = tun_send::Config { to_tcp, mtu };
+
+    init_logger();
+
+    tun_send::send_tun(config, &tun);
+}
+
+fn init_logger() {
+    if env::var("RUST_LOG").is_ok() {
+        simple_logger::init_with_env()
+    } else {
+        simple_logger::init_with_level(log::Level::Info)
+    }
+    .expect("logger initialization")
+}
@@ -0,0 +1,278 @@
+use clap::{Arg, ArgAction, Command};
+use crossbeam_channel::{unbounded, Sender};
+use diode::{protocol, send::udp_send, ClientId, Message};
+use log::{error, info};
+use raptorq::{Encoder, EncodingPacket};
+use std::{
+    env, fmt, io,
+    io::Read,
+    net::{self, SocketAddr, TcpStream},
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+};
+
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
+struct Config {
+    from_tcp: SocketAddr,
+    paths: Vec<(SocketAddr, SocketAddr)>,
+    mtu: u16,
+    encoding_block_size: u64,
+    repair_block_size: u32,
+    rate_limit: u64,
+}
+
+fn command_args() -> Config {
+    let args = Command::new(env!("CARGO_BIN_NAME"))
+        .version(env!("CARGO_PKG_VERSION"))
+        .arg(
+            Arg::new("from_tcp")
+                .long("from_tcp")
+                .value_name("ip:port")
+                .default_value("127.0.0.1:5000")
+                .help("Where to listen for incoming TCP transfers"),
+        )
+        .arg(
+            Arg::new("to_bind")
+                .long("to_bind")
+                .value_name("ip:port")
+                .default_value("0.0.0.0:0")
+                .action(ArgAction::Append)
+                .help("Local address to bind a UDP sending path to; repeat alongside --to_udp for multipath"),
+        )
+        .arg(
+            Arg::new("to_udp")
+                .long("to_udp")
+                .value_name("ip:port")
+                .default_value("127.0.0.1:6000")
+                .action(ArgAction::Append)
+                .help("Where to send UDP traffic; repeat to stripe across several paths"),
+        )
+        .arg(
+            Arg::new("mtu")
+                .long("mtu")
+                .value_name("nb_bytes")
+                .default_value("1500")
+                .value_parser(clap::value_parser!(u16))
+                .help("MTU of the outgoing UDP link"),
+        )
+        .arg(
+            Arg::new("encoding_block_size")
+                .long("encoding_block_size")
+                .value_name("nb_bytes")
+                .default_value("60000") // (mtu * 40), optimal parameter -- to align with other size !
+                .value_parser(clap::value_parser!(u64))
+                .help("Size of RaptorQ block"),
+        )
+        .arg(
+            Arg::new("repair_block_size")
+                .long("repair_block_size")
+                .value_name("ratior")
+                .default_value("6000") // mtu * 4
+                .value_parser(clap::value_parser!(u32))
+                .help("Size of repair data in bytes"),
+        )
+        .arg(
+            Arg::new("rate_limit")
+                .long("rate_limit")
+                .value_name("bytes_per_sec")
+                .default_value("0")
+                .value_parser(clap::value_parser!(u64))
+                .help("Bytes per second to pace UDP output at; 0 disables pacing"),
+        )
+        .get_matches();
+
+    let from_tcp = SocketAddr::from_str(args.get_one::<String>("from_tcp").expect("default"))
+        .expect("invalid from_tcp parameter");
+
+    let to_bind: Vec<SocketAddr> = args
+        .get_many::<String>("to_bind")
+        .expect("default")
+        .map(|addr| SocketAddr::from_str(addr).expect("invalid to_bind parameter"))
+        .collect();
+    let to_udp: Vec<SocketAddr> = args
+        .get_many::<String>("to_udp")
+        .expect("default")
+        .map(|addr| SocketAddr::from_str(addr).expect("invalid to_udp parameter"))
+        .collect();
+    assert!(
+        !to_bind.is_empty() && !to_udp.is_empty(),
+        "at least one --to_bind/--to_udp pair is required"
+    );
+    assert_eq!(
+        to_bind.len(),
+        to_udp.len(),
+        "--to_bind and --to_udp must be given the same number of times, one pair per path"
+    );
+    let paths: Vec<(SocketAddr, SocketAddr)> = to_bind.into_iter().zip(to_udp).collect();
+
+    let mtu = *args.get_one::<u16>("mtu").expect("default");
+    let encoding_block_size = *args.get_one::<u64>("encoding_block_size").expect("default");
+    let repair_block_size = *args.get_one::<u32>("repair_block_size").expect("default");
+    let rate_limit = *args.get_one::<u64>("rate_limit").expect("default");
+
+    Config {
+        from_tcp,
+        paths,
+        mtu,
+        encoding_block_size,
+        repair_block_size,
+        rate_limit,
+    }
+}
+
+enum Error {
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::Io(e) => write!(fmt, "I/O error: {e}"),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+fn main_loop(config: Config) -> Result<(), Error> {
+    let object_transmission_info =
+        protocol::object_transmission_information(config.mtu, config.encoding_block_size);
+    let max_messages = protocol::nb_encoding_packets(&object_transmission_info) as u16
+        + protocol::nb_repair_packets(&object_transmission_info, config.repair_block_size) as u16;
+
+    let (encoding_sendq, encoding_recvq) = unbounded::<Vec<EncodingPacket>>();
+
+    let udp_send_config = udp_send::Config {
+        paths: config.paths,
+        mtu: config.mtu,
+        max_messages,
+        encoding_block_size: config.encoding_block_size,
+        repair_block_size: config.repair_block_size,
+        rate_limit: config.rate_limit,
+    };
+
+    thread::Builder::new()
+        .name("diode-udp-send".to_string())
+        .spawn(move || udp_send::new(udp_send_config, &encoding_recvq))
+        .expect("thread spawn");
+
+    info!("listening for TCP transfers at {}", config.from_tcp);
+    let listener = net::TcpListener::bind(config.from_tcp)?;
+
+    for stream in listener.incoming() {
+        let stream = stream?;
+        let encoding_sendq = encoding_sendq.clone();
+        let encoding_block_size = config.encoding_block_size;
+        let mtu = config.mtu;
+        let repair_block_size = config.repair_block_size;
+        let client_id: ClientId = NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed);
+
+        thread::spawn(move || {
+            if let Err(e) = encode_client(
+                stream,
+                client_id,
+                encoding_block_size,
+                mtu,
+                repair_block_size,
+                &encoding_sendq,
+            ) {
+                error!("client {client_id:x} encoding loop error: {e}");
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Reads chunks of up to `encoding_block_size` minus the envelope overhead
+/// off `stream`, wraps each one as a `Message::Data` payload in a
+/// `ClientMessage` envelope tagged with `client_id`, RaptorQ-encodes
+/// the envelope and hands the resulting source+repair packets to `sendq` for
+/// transmission over the (possibly multipath) UDP link. A `Start` envelope
+/// opens the transfer and an `End` envelope closes it, so `receive::dispatch`
+/// on the other side knows when to spin up and tear down the per-client sink.
+fn encode_client(
+    mut stream: TcpStream,
+    client_id: ClientId,
+    encoding_block_size: u64,
+    mtu: u16,
+    repair_block_size: u32,
+    sendq: &Sender<Vec<EncodingPacket>>,
+) -> io::Result<()> {
+    let max_data_len = encoding_block_size as usize - protocol::Message::serialize_overhead();
+    let mut buffer = vec![0u8; max_data_len];
+
+    if !send_envelope(client_id, Message::Start, mtu, repair_block_size, sendq) {
+        return Ok(());
+    }
+
+    loop {
+        let nb_bytes = read_block(&mut stream, &mut buffer)?;
+        if nb_bytes == 0 {
+            send_envelope(client_id, Message::End, mtu, repair_block_size, sendq);
+            return Ok(());
+        }
+
+        let payload = Message::Data(buffer[..nb_bytes].to_vec());
+        if !send_envelope(client_id, payload, mtu, repair_block_size, sendq) {
+            return Ok(());
+        }
+    }
+}
+
+/// Bincode-serializes one `ClientMessage` envelope, RaptorQ-encodes it on
+/// its own and queues the resulting packets for transmission. Returns
+/// `false` once `sendq` is gone, so callers can stop feeding a dead link.
+fn send_envelope(
+    client_id: ClientId,
+    payload: Message,
+    mtu: u16,
+    repair_block_size: u32,
+    sendq: &Sender<Vec<EncodingPacket>>,
+) -> bool {
+    let message = protocol::Message { client_id, payload };
+    let mut buffer = Vec::new();
+    bincode::serialize_into(&mut buffer, &message).expect("envelope serialization");
+
+    let encoder = Encoder::with_defaults(&buffer, mtu);
+    let nb_repair_packets = repair_block_size / u32::from(mtu);
+    let packets = encoder.get_encoded_packets(nb_repair_packets);
+
+    sendq.send(packets).is_ok()
+}
+
+fn read_block(stream: &mut TcpStream, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut nb_read = 0;
+    while nb_read < buffer.len() {
+        match stream.read(&mut buffer[nb_read..])? {
+            0 => break,
+            n => nb_read += n,
+        }
+    }
+    Ok(nb_read)
+}
+
+fn main() {
+    let config = command_args();
+
+    init_logger();
+
+    if let Err(e) = main_loop(config) {
+        error!("failed to launch main_loop: {e}");
+    }
+}
+
+fn init_logger() {
+    if env::var("RUST_LOG").is_ok() {
+        simple_logger::init_with_env()
+    } else {
+        simple_logger::init_with_level(log::Level::Info)
+    }
+    .expect("logger initialization")
+}
@@ -0,0 +1,11 @@
+pub mod send;
+
+use std::net::SocketAddr;
+
+pub struct Config {
+    pub socket_addr: SocketAddr,
+    pub buffer_size: usize,
+    /// Memory-map regular files and write directly from the mapped slices
+    /// instead of going through a heap read buffer.
+    pub mmap: bool,
+}
@@ -0,0 +1,83 @@
+use crate::file::Config;
+use log::info;
+use memmap2::Mmap;
+use std::{
+    fmt,
+    fs::File,
+    io::{self, Read, Write},
+    net::TcpStream,
+};
+
+pub enum Error {
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::Io(e) => write!(fmt, "I/O error: {e}"),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+pub fn send_files(config: Config, files: Vec<String>) -> Result<(), Error> {
+    for file in files {
+        send_file(&config, &file)?;
+    }
+    Ok(())
+}
+
+fn send_file(config: &Config, path: &str) -> Result<(), Error> {
+    let mut socket = TcpStream::connect(config.socket_addr)?;
+
+    if path == "-" {
+        info!("sending stdin to {}", config.socket_addr);
+        return send_buffered(config, &mut io::stdin(), &mut socket);
+    }
+
+    info!("sending {path} to {}", config.socket_addr);
+    let mut file = File::open(path)?;
+    let is_regular_nonempty = file.metadata()?.is_file() && file.metadata()?.len() > 0;
+
+    if config.mmap && is_regular_nonempty {
+        send_mmap(config, &file, &mut socket)
+    } else {
+        send_buffered(config, &mut file, &mut socket)
+    }
+}
+
+/// Memory-maps `file` and writes it in `buffer_size`-sized windows, letting
+/// the kernel page the file in on demand instead of doubling memory traffic
+/// through a read buffer. `Mmap::map` errors on zero-length files, so the
+/// caller falls back to `send_buffered` for those.
+fn send_mmap(config: &Config, file: &File, socket: &mut TcpStream) -> Result<(), Error> {
+    let mmap = unsafe { Mmap::map(file)? };
+
+    for window in mmap.chunks(config.buffer_size.max(1)) {
+        socket.write_all(window)?;
+    }
+
+    Ok(())
+}
+
+fn send_buffered<R: Read>(
+    config: &Config,
+    reader: &mut R,
+    socket: &mut TcpStream,
+) -> Result<(), Error> {
+    let mut buffer = vec![0u8; config.buffer_size];
+
+    loop {
+        let nb_bytes = reader.read(&mut buffer)?;
+        if nb_bytes == 0 {
+            return Ok(());
+        }
+        socket.write_all(&buffer[..nb_bytes])?;
+    }
+}
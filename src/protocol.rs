@@ -0,0 +1,35 @@
+use raptorq::ObjectTransmissionInformation;
+
+/// Per-client envelope flowing out of `receive::decoding` and demuxed by
+/// `receive::dispatch` to `tcp_serve`/`tun_serve`.
+pub type Message = crate::ClientMessage;
+
+impl Message {
+    /// Serialized size of the framing (client id, payload discriminant and
+    /// length prefix) that wraps every envelope on the wire, so callers can
+    /// size a RaptorQ block around `encoding_block_size` without truncating
+    /// the payload it is meant to carry.
+    pub fn serialize_overhead() -> usize {
+        // u64 client id + u32 variant tag + u64 `Vec` length, bincode fixint encoding
+        8 + 4 + 8
+    }
+}
+
+pub fn object_transmission_information(
+    mtu: u16,
+    encoding_block_size: u64,
+) -> ObjectTransmissionInformation {
+    ObjectTransmissionInformation::with_defaults(encoding_block_size, mtu)
+}
+
+pub fn packet_size(oti: &ObjectTransmissionInformation) -> u16 {
+    oti.symbol_size()
+}
+
+pub fn nb_encoding_packets(oti: &ObjectTransmissionInformation) -> u64 {
+    oti.transfer_length().div_ceil(u64::from(oti.symbol_size()))
+}
+
+pub fn nb_repair_packets(oti: &ObjectTransmissionInformation, repair_block_size: u32) -> u64 {
+    u64::from(repair_block_size) / u64::from(oti.symbol_size())
+}
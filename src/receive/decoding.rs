@@ -0,0 +1,75 @@
+use crate::protocol;
+use crate::stats::Stats;
+use crossbeam_channel::{Receiver, Sender};
+use log::warn;
+use raptorq::{Decoder, EncodingPacket, ObjectTransmissionInformation};
+use std::io::Cursor;
+use std::sync::Mutex;
+
+pub struct Config {
+    pub object_transmission_info: ObjectTransmissionInformation,
+}
+
+/// RaptorQ-decodes each reblocked group of packets and hands the resulting
+/// stream of per-client envelopes downstream to `dispatch`.
+pub fn new(
+    config: &Config,
+    block_to_receive: &Mutex<u8>,
+    recvq: &Receiver<(u8, Vec<EncodingPacket>)>,
+    sendq: &Sender<protocol::Message>,
+    stats: &Stats,
+) {
+    loop {
+        let Ok((block_id, packets)) = recvq.recv() else {
+            return;
+        };
+
+        // Records the block after this one as the next to receive; `reblock`
+        // writes the same value when it hands a block over.
+        *block_to_receive.lock().expect("poisoned mutex") = block_id.wrapping_add(1);
+
+        let nb_encoding_packets = protocol::nb_encoding_packets(&config.object_transmission_info);
+
+        let mut decoder = Decoder::new(config.object_transmission_info);
+        let mut nb_packets_consumed = 0u64;
+        let mut decoded = None;
+        for packet in packets {
+            nb_packets_consumed += 1;
+            if let Some(data) = decoder.decode(packet) {
+                decoded = Some(data);
+                break;
+            }
+        }
+
+        match decoded {
+            Some(data) => {
+                let nb_repair_packets_used =
+                    nb_packets_consumed.saturating_sub(nb_encoding_packets);
+                stats.record_block_decoded(nb_repair_packets_used);
+                for message in deserialize_messages(&data) {
+                    if sendq.send(message).is_err() {
+                        return;
+                    }
+                }
+            }
+            None => {
+                stats.record_block_failed();
+                warn!("failed to decode block {block_id:x}, too many symbols lost");
+            }
+        }
+    }
+}
+
+fn deserialize_messages(data: &[u8]) -> Vec<protocol::Message> {
+    let mut cursor = Cursor::new(data);
+    let mut messages = Vec::new();
+
+    while (cursor.position() as usize) < data.len() {
+        match bincode::deserialize_from(&mut cursor) {
+            Ok(message) => messages.push(message),
+            Err(_) => break,
+        }
+    }
+
+    messages
+}
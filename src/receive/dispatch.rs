@@ -0,0 +1,89 @@
+use crate::protocol;
+use crate::stats::Stats;
+use crate::{tcp_serve, tun_serve};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use log::warn;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Clone)]
+pub enum Sink {
+    Tcp {
+        to_tcp: SocketAddr,
+        to_tcp_buffer_size: usize,
+        abort_timeout: Duration,
+    },
+    Tun {
+        ifname: String,
+        mtu: u16,
+    },
+}
+
+pub struct Config {
+    pub nb_multiplex: u16,
+    pub sink: Sink,
+    pub heartbeat: Duration,
+}
+
+/// Demuxes the per-client envelope stream coming out of `decoding` and
+/// forwards each client's payloads to a dedicated `tcp_serve`/`tun_serve`
+/// worker.
+pub fn new(config: Config, recvq: Receiver<protocol::Message>, stats: Arc<Stats>) {
+    let mut active_transfers: HashMap<crate::ClientId, Sender<crate::Message>> = HashMap::new();
+
+    loop {
+        let Ok(message) = recvq.recv() else {
+            return;
+        };
+
+        if let crate::Message::Start = message.payload {
+            if active_transfers.len() >= config.nb_multiplex as usize {
+                warn!(
+                    "rejecting transfer from client {:x}: {} simultaneous transfers already active",
+                    message.client_id, config.nb_multiplex
+                );
+                continue;
+            }
+
+            let (client_sendq, client_recvq) = unbounded::<crate::Message>();
+            active_transfers.insert(message.client_id, client_sendq);
+
+            let sink = config.sink.clone();
+            let stats = Arc::clone(&stats);
+            let client_id = message.client_id;
+
+            thread::spawn(move || match sink {
+                Sink::Tcp {
+                    to_tcp,
+                    to_tcp_buffer_size,
+                    abort_timeout,
+                } => {
+                    let tcp_serve_config = tcp_serve::Config {
+                        to_tcp,
+                        to_tcp_buffer_size,
+                        abort_timeout: abort_timeout.as_secs(),
+                    };
+                    tcp_serve::new(tcp_serve_config, client_id, client_recvq, &stats)
+                }
+                Sink::Tun { ifname, mtu } => {
+                    let tun_serve_config = tun_serve::Config { ifname, mtu };
+                    tun_serve::new(tun_serve_config, client_id, client_recvq, &stats)
+                }
+            });
+
+            continue;
+        }
+
+        let Some(sendq) = active_transfers.get(&message.client_id) else {
+            continue;
+        };
+
+        let will_end = matches!(message.payload, crate::Message::Abort | crate::Message::End);
+        if sendq.send(message.payload).is_err() || will_end {
+            active_transfers.remove(&message.client_id);
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod decoding;
+pub mod dispatch;
+pub mod reblock;
@@ -0,0 +1,72 @@
+use crate::protocol;
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use raptorq::{EncodingPacket, ObjectTransmissionInformation};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub struct Config {
+    pub object_transmission_info: ObjectTransmissionInformation,
+    pub repair_block_size: u32,
+    pub flush_timeout: Duration,
+}
+
+struct Block {
+    packets: Vec<EncodingPacket>,
+    first_seen: Instant,
+}
+
+/// Groups incoming `EncodingPacket`s by source block number so `decoding`
+/// can RaptorQ-decode one block at a time. Packets from different UDP
+/// paths interleave freely here: block/ESI is carried in the packet
+/// itself, so reordering across paths is harmless.
+pub fn new(
+    config: &Config,
+    block_to_receive: &Mutex<u8>,
+    recvq: &Receiver<Vec<EncodingPacket>>,
+    sendq: &Sender<(u8, Vec<EncodingPacket>)>,
+) {
+    let block_size = protocol::nb_encoding_packets(&config.object_transmission_info)
+        + protocol::nb_repair_packets(&config.object_transmission_info, config.repair_block_size);
+
+    let mut blocks: HashMap<u8, Block> = HashMap::new();
+
+    loop {
+        match recvq.recv_timeout(config.flush_timeout) {
+            Ok(packets) => {
+                for packet in packets {
+                    let block_id = packet.payload_id().source_block_number();
+                    blocks
+                        .entry(block_id)
+                        .or_insert_with(|| Block {
+                            packets: Vec::new(),
+                            first_seen: Instant::now(),
+                        })
+                        .packets
+                        .push(packet);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        let ready: Vec<u8> = blocks
+            .iter()
+            .filter(|(_, block)| {
+                block.packets.len() as u64 >= block_size
+                    || block.first_seen.elapsed() >= config.flush_timeout
+            })
+            .map(|(block_id, _)| *block_id)
+            .collect();
+
+        for block_id in ready {
+            let Some(block) = blocks.remove(&block_id) else {
+                continue;
+            };
+            *block_to_receive.lock().expect("poisoned mutex") = block_id.wrapping_add(1);
+            if sendq.send((block_id, block.packets)).is_err() {
+                return;
+            }
+        }
+    }
+}
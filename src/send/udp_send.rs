@@ -5,15 +5,58 @@ use raptorq::EncodingPacket;
 use std::{
     fmt, io,
     net::{SocketAddr, UdpSocket},
+    thread,
+    time::Instant,
 };
 
 pub struct Config {
-    pub to_bind: SocketAddr,
-    pub to_udp: SocketAddr,
+    /// One (to_bind, to_udp) pair per path to stripe packets across. RaptorQ
+    /// packets already carry their block/ESI, so reordering across paths is
+    /// harmless and `reblock` regroups them on the receive side.
+    pub paths: Vec<(SocketAddr, SocketAddr)>,
     pub mtu: u16,
     pub max_messages: u16,
     pub encoding_block_size: u64,
     pub repair_block_size: u32,
+    /// Bytes per second to pace output at; zero/unset bypasses the token bucket.
+    pub rate_limit: u64,
+}
+
+/// Paces outgoing bytes to `rate_limit` bytes/sec so a fixed-rate diode link
+/// or a small receive buffer isn't overrun by bursty `send_mmsg` calls.
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64, capacity: u64) -> Self {
+        Self {
+            rate: rate as f64,
+            capacity: capacity as f64,
+            tokens: capacity as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn pace(&mut self, nb_bytes: usize) {
+        let now = Instant::now();
+        let elapsed_secs = now.duration_since(self.last_refill).as_secs_f64();
+        self.last_refill = now;
+
+        self.tokens = (self.tokens + elapsed_secs * self.rate).min(self.capacity);
+
+        let nb_bytes = nb_bytes as f64;
+        if self.tokens < nb_bytes {
+            let wait_secs = (nb_bytes - self.tokens) / self.rate;
+            thread::sleep(std::time::Duration::from_secs_f64(wait_secs));
+            self.tokens = nb_bytes;
+        }
+
+        self.tokens -= nb_bytes;
+    }
 }
 
 enum Error {
@@ -49,26 +92,61 @@ pub fn new(config: Config, recvq: &Receiver<Vec<EncodingPacket>>) {
 }
 
 fn main_loop(config: Config, recvq: &Receiver<Vec<EncodingPacket>>) -> Result<(), Error> {
-    info!(
-        "sending UDP traffic to {} with MTU {} binding to {}",
-        config.to_udp, config.mtu, config.to_bind
-    );
-    let socket = UdpSocket::bind(config.to_bind)?;
-    sock_utils::set_socket_send_buffer_size(&socket, i32::MAX)?;
-    let sock_buffer_size = sock_utils::get_socket_send_buffer_size(&socket)?;
-    log::info!("UDP socket send buffer size set to {sock_buffer_size}");
-    if (sock_buffer_size as u64)
-        < 2 * (config.encoding_block_size + config.repair_block_size as u64)
-    {
-        log::warn!("UDP socket send buffer may be too small to achieve optimal performances");
-        log::warn!("Please review the kernel parameters using sysctl");
+    let mut senders = Vec::with_capacity(config.paths.len());
+    for (to_bind, to_udp) in &config.paths {
+        info!(
+            "sending UDP traffic to {to_udp} with MTU {} binding to {to_bind}",
+            config.mtu
+        );
+        let socket = UdpSocket::bind(to_bind)?;
+        sock_utils::set_socket_send_buffer_size(&socket, i32::MAX)?;
+        let sock_buffer_size = sock_utils::get_socket_send_buffer_size(&socket)?;
+        log::info!("UDP socket send buffer size set to {sock_buffer_size}");
+        if (sock_buffer_size as u64)
+            < 2 * (config.encoding_block_size + config.repair_block_size as u64)
+        {
+            log::warn!("UDP socket send buffer may be too small to achieve optimal performances");
+            log::warn!("Please review the kernel parameters using sysctl");
+        }
+
+        senders.push(udp::UdpMessages::new_sender(
+            socket,
+            usize::from(config.max_messages),
+            *to_udp,
+        ));
     }
 
-    let mut udp_messages =
-        udp::UdpMessages::new_sender(socket, usize::from(config.max_messages), config.to_udp);
+    let mut token_bucket = (config.rate_limit > 0).then(|| {
+        let burst = config.encoding_block_size + config.repair_block_size as u64;
+        info!(
+            "pacing UDP output at {} bytes/sec with a burst of {burst} bytes",
+            config.rate_limit
+        );
+        TokenBucket::new(config.rate_limit, burst)
+    });
+
+    let nb_paths = senders.len();
+    let mut next_path = 0usize;
 
     loop {
         let packets = recvq.recv()?;
-        udp_messages.send_mmsg(packets.iter().map(EncodingPacket::serialize).collect())?;
+        let serialized: Vec<Vec<u8>> = packets.iter().map(EncodingPacket::serialize).collect();
+
+        if let Some(token_bucket) = &mut token_bucket {
+            let nb_bytes = serialized.iter().map(Vec::len).sum();
+            token_bucket.pace(nb_bytes);
+        }
+
+        let mut per_path: Vec<Vec<Vec<u8>>> = vec![Vec::new(); nb_paths];
+        for packet in serialized {
+            per_path[next_path % nb_paths].push(packet);
+            next_path = next_path.wrapping_add(1);
+        }
+
+        for (sender, batch) in senders.iter_mut().zip(per_path) {
+            if !batch.is_empty() {
+                sender.send_mmsg(batch)?;
+            }
+        }
     }
 }
@@ -0,0 +1,107 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Live counters updated from the hot loops of the receive pipeline.
+///
+/// This is the only feedback operators of a blind one-way link get about
+/// whether `repair_block_size` is tuned correctly for the observed loss.
+#[derive(Default)]
+pub struct Stats {
+    pub udp_bytes_received: AtomicU64,
+    pub udp_packets_received: AtomicU64,
+    pub tcp_bytes_sent: AtomicU64,
+
+    pub blocks_decoded: AtomicU64,
+    pub blocks_failed: AtomicU64,
+    pub repair_packets_consumed: AtomicU64,
+}
+
+impl Stats {
+    pub fn record_udp_recv(&self, nb_packets: u64, nb_bytes: u64) {
+        self.udp_packets_received
+            .fetch_add(nb_packets, Ordering::Relaxed);
+        self.udp_bytes_received
+            .fetch_add(nb_bytes, Ordering::Relaxed);
+    }
+
+    /// Called by the egress side (`tcp_serve`/`tun_serve`) for every chunk
+    /// successfully delivered to the final destination.
+    pub fn record_tcp_sent(&self, nb_bytes: u64) {
+        self.tcp_bytes_sent.fetch_add(nb_bytes, Ordering::Relaxed);
+    }
+
+    /// Called by `decoding` once a block's RaptorQ decode succeeds.
+    pub fn record_block_decoded(&self, nb_repair_packets_used: u64) {
+        self.blocks_decoded.fetch_add(1, Ordering::Relaxed);
+        self.repair_packets_consumed
+            .fetch_add(nb_repair_packets_used, Ordering::Relaxed);
+    }
+
+    /// Called by `decoding` when too many source symbols were lost to
+    /// reconstruct the block even with the available repair packets.
+    pub fn record_block_failed(&self) {
+        self.blocks_failed.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+pub struct Config {
+    pub interval: Duration,
+    pub nb_encoding_packets: u64,
+}
+
+/// Periodically logs ingress/egress throughput and FEC effectiveness
+/// computed from the counters in `stats`.
+pub fn new(config: Config, stats: &Stats) {
+    let mut last = Instant::now();
+    let mut last_udp_bytes = 0;
+    let mut last_tcp_bytes = 0;
+    let mut last_udp_packets = 0;
+    let mut last_blocks_decoded = 0;
+    let mut last_blocks_failed = 0;
+
+    loop {
+        std::thread::sleep(config.interval);
+
+        let now = Instant::now();
+        let elapsed_secs = now.duration_since(last).as_secs_f64();
+        last = now;
+
+        let udp_bytes = stats.udp_bytes_received.load(Ordering::Relaxed);
+        let udp_packets = stats.udp_packets_received.load(Ordering::Relaxed);
+        let tcp_bytes = stats.tcp_bytes_sent.load(Ordering::Relaxed);
+        let blocks_decoded = stats.blocks_decoded.load(Ordering::Relaxed);
+        let blocks_failed = stats.blocks_failed.load(Ordering::Relaxed);
+
+        let ingress_mbps = (udp_bytes - last_udp_bytes) as f64 * 8.0 / elapsed_secs / 1_000_000.0;
+        let egress_mbps = (tcp_bytes - last_tcp_bytes) as f64 * 8.0 / elapsed_secs / 1_000_000.0;
+
+        let new_blocks =
+            (blocks_decoded - last_blocks_decoded) + (blocks_failed - last_blocks_failed);
+        let decode_success_ratio = if new_blocks > 0 {
+            (blocks_decoded - last_blocks_decoded) as f64 / new_blocks as f64
+        } else {
+            1.0
+        };
+
+        let new_packets = udp_packets - last_udp_packets;
+        let expected_packets = new_blocks * config.nb_encoding_packets;
+        let packet_loss_ratio = if expected_packets > 0 {
+            1.0 - (new_packets as f64 / expected_packets as f64).min(1.0)
+        } else {
+            0.0
+        };
+
+        log::info!(
+            "stats: ingress {ingress_mbps:.2} Mbps, egress {egress_mbps:.2} Mbps, \
+             decode success {:.1}%, estimated packet loss {:.1}%",
+            decode_success_ratio * 100.0,
+            packet_loss_ratio * 100.0,
+        );
+
+        last_udp_bytes = udp_bytes;
+        last_tcp_bytes = tcp_bytes;
+        last_udp_packets = udp_packets;
+        last_blocks_decoded = blocks_decoded;
+        last_blocks_failed = blocks_failed;
+    }
+}
@@ -0,0 +1,75 @@
+use crate::stats::Stats;
+use crossbeam_channel::{Receiver, RecvTimeoutError};
+use log::{debug, error};
+use std::{
+    fmt, io,
+    io::{BufWriter, Write},
+    net::{SocketAddr, TcpStream},
+    time::Duration,
+};
+
+pub(crate) struct Config {
+    pub to_tcp: SocketAddr,
+    pub to_tcp_buffer_size: usize,
+    pub abort_timeout: u64,
+}
+
+enum Error {
+    Io(io::Error),
+    AbortTimeout,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::Io(e) => write!(fmt, "I/O error: {e}"),
+            Self::AbortTimeout => write!(fmt, "no data received before abort timeout"),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+pub(crate) fn new(
+    config: Config,
+    client_id: crate::ClientId,
+    client_recvq: Receiver<crate::Message>,
+    stats: &Stats,
+) {
+    if let Err(e) = main_loop(&config, client_recvq, stats) {
+        error!("tcp_serve loop error for client {client_id:x}: {e}");
+    }
+}
+
+fn main_loop(
+    config: &Config,
+    client_recvq: Receiver<crate::Message>,
+    stats: &Stats,
+) -> Result<(), Error> {
+    let socket = TcpStream::connect(config.to_tcp)?;
+    let mut socket = BufWriter::with_capacity(config.to_tcp_buffer_size, socket);
+    let abort_timeout = Duration::from_secs(config.abort_timeout);
+
+    loop {
+        match client_recvq.recv_timeout(abort_timeout) {
+            Ok(crate::Message::Data(payload)) => {
+                socket.write_all(&payload)?;
+                stats.record_tcp_sent(payload.len() as u64);
+            }
+            Ok(crate::Message::Abort | crate::Message::End) => {
+                socket.flush()?;
+                debug!("tcp transfer ended");
+                return Ok(());
+            }
+            Ok(crate::Message::Start | crate::Message::Heartbeat | crate::Message::Padding(_)) => {
+                continue;
+            }
+            Err(RecvTimeoutError::Timeout) => return Err(Error::AbortTimeout),
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::fd::AsRawFd;
+
+const IFNAMSIZ: usize = 16;
+// _IOW('T', 202, int), see linux/if_tun.h
+const TUNSETIFF: libc::c_ulong = 0x4004_54ca;
+const IFF_TUN: libc::c_short = 0x0001;
+const IFF_NO_PI: libc::c_short = 0x1000;
+
+#[repr(C)]
+struct IfReq {
+    ifr_name: [libc::c_char; IFNAMSIZ],
+    ifr_flags: libc::c_short,
+    _padding: [u8; 22],
+}
+
+/// A TUN device handing whole IP packets, one per `read`/`write` call.
+pub struct TunDevice {
+    file: File,
+}
+
+impl TunDevice {
+    pub fn open(ifname: &str) -> io::Result<Self> {
+        if ifname.len() >= IFNAMSIZ {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "interface name too long",
+            ));
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open("/dev/net/tun")?;
+
+        let mut ifr_name = [0 as libc::c_char; IFNAMSIZ];
+        for (dst, src) in ifr_name.iter_mut().zip(ifname.bytes()) {
+            *dst = src as libc::c_char;
+        }
+
+        let ifr = IfReq {
+            ifr_name,
+            ifr_flags: IFF_TUN | IFF_NO_PI,
+            _padding: [0; 22],
+        };
+
+        let res = unsafe { libc::ioctl(file.as_raw_fd(), TUNSETIFF, &ifr) };
+        if res < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self { file })
+    }
+
+    /// Reads a single IP packet into `buf`, returning the populated slice.
+    pub fn read_packet<'a>(&mut self, buf: &'a mut [u8]) -> io::Result<&'a [u8]> {
+        let nb_bytes = self.file.read(buf)?;
+        Ok(&buf[..nb_bytes])
+    }
+
+    /// Writes a single IP packet back into the kernel.
+    pub fn write_packet(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.file.write_all(packet)
+    }
+}
@@ -0,0 +1,70 @@
+use crate::tun::TunDevice;
+use log::{error, warn};
+use std::{
+    fmt, io,
+    io::Write,
+    net::{SocketAddr, TcpStream},
+};
+
+pub struct Config {
+    pub to_tcp: SocketAddr,
+    pub mtu: u16,
+}
+
+enum Error {
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+        match self {
+            Self::Io(e) => write!(fmt, "I/O error: {e}"),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Reads IP packets off `ifname` and forwards each one, length-prefixed, to
+/// `config.to_tcp`. The encoding side cuts that byte stream into chunks that
+/// are not packet-aligned; the length prefix lets `tun_serve` split the
+/// reassembled stream back into packets.
+pub fn send_tun(config: Config, ifname: &str) {
+    if let Err(e) = main_loop(config, ifname) {
+        error!("TUN send loop error: {e}");
+    }
+}
+
+fn main_loop(config: Config, ifname: &str) -> Result<(), Error> {
+    let mut tun = TunDevice::open(ifname)?;
+
+    log::info!("reading IP packets from {ifname}, mtu {}", config.mtu);
+    log::info!("forwarding to {}", config.to_tcp);
+
+    let mut socket = TcpStream::connect(config.to_tcp)?;
+
+    // Sized above any IP packet the kernel can hand us (max 65535 bytes),
+    // not just `config.mtu`, so a packet that exceeds the MTU shows up as
+    // an oversized read here instead of being silently truncated to it.
+    let mut buffer = vec![0u8; 65536];
+
+    loop {
+        let packet = tun.read_packet(&mut buffer)?;
+
+        if packet.len() > config.mtu as usize {
+            warn!(
+                "dropping oversized packet of {} bytes (mtu is {})",
+                packet.len(),
+                config.mtu
+            );
+            continue;
+        }
+
+        socket.write_all(&(packet.len() as u16).to_be_bytes())?;
+        socket.write_all(packet)?;
+    }
+}
@@ -0,0 +1,80 @@
+use crate::stats::Stats;
+use crate::tun::TunDevice;
+use crossbeam_channel::Receiver;
+use log::{debug, error, warn};
+
+#[derive(Clone)]
+pub(crate) struct Config {
+    pub ifname: String,
+    pub mtu: u16,
+}
+
+pub(crate) fn new(
+    config: Config,
+    client_id: crate::ClientId,
+    client_recvq: Receiver<crate::Message>,
+    stats: &Stats,
+) {
+    if let Err(e) = main_loop(&config, client_recvq, stats) {
+        error!("tun_serve loop error for client {client_id:x}: {e}");
+    }
+}
+
+fn main_loop(
+    config: &Config,
+    client_recvq: Receiver<crate::Message>,
+    stats: &Stats,
+) -> Result<(), std::io::Error> {
+    let mut tun = TunDevice::open(&config.ifname)?;
+
+    // `Data` chunks are arbitrary, non-packet-aligned slices of the
+    // reassembled TCP byte stream produced by `tun_send`, which prefixes
+    // every IP packet with its length as a big-endian u16. Buffer the
+    // stream here and peel off complete frames as they become available.
+    let mut buffer = Vec::new();
+
+    loop {
+        match client_recvq.recv() {
+            Ok(crate::Message::Data(payload)) => {
+                buffer.extend_from_slice(&payload);
+
+                loop {
+                    let Some(frame) = take_frame(&mut buffer) else {
+                        break;
+                    };
+                    if frame.len() > config.mtu as usize {
+                        warn!(
+                            "dropping oversized packet of {} bytes reassembled from the diode",
+                            frame.len()
+                        );
+                        continue;
+                    }
+                    tun.write_packet(&frame)?;
+                    stats.record_tcp_sent(frame.len() as u64);
+                }
+            }
+            Ok(crate::Message::Abort | crate::Message::End) => {
+                debug!("tun transfer ended");
+                return Ok(());
+            }
+            Ok(_) => continue,
+            Err(_) => return Ok(()),
+        }
+    }
+}
+
+/// Pops one length-prefixed IP packet off the front of `buffer`, if a full
+/// frame is available, leaving any trailing partial frame in place for the
+/// next `Data` chunk to complete.
+fn take_frame(buffer: &mut Vec<u8>) -> Option<Vec<u8>> {
+    if buffer.len() < 2 {
+        return None;
+    }
+    let len = u16::from_be_bytes([buffer[0], buffer[1]]) as usize;
+
+    if buffer.len() < 2 + len {
+        return None;
+    }
+
+    Some(buffer.drain(..2 + len).skip(2).collect())
+}